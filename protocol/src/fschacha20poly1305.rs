@@ -1,5 +1,7 @@
 use alloc::{fmt, vec::Vec};
 
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
 use crate::chacha20poly1305::chacha20::ChaCha20;
 use crate::chacha20poly1305::ChaCha20Poly1305;
 
@@ -41,10 +43,10 @@ pub enum CryptType {
 /// nonces and re-keying.
 ///
 /// FSChaCha20Poly1305 is used for message packets in BIP324.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Zeroize, ZeroizeOnDrop)]
 pub struct FSChaCha20Poly1305 {
     key: [u8; 32],
-    message_counter: u32,
+    message_counter: u64,
 }
 
 impl FSChaCha20Poly1305 {
@@ -61,11 +63,12 @@ impl FSChaCha20Poly1305 {
         contents: Vec<u8>,
         crypt_type: CryptType,
     ) -> Result<Vec<u8>, Error> {
-        let mut counter_div = (self.message_counter / REKEY_INTERVAL)
-            .to_le_bytes()
-            .to_vec();
-        counter_div.extend([0u8; 4]); // ok? invalid for 4 billion messages
-        let counter_mod = (self.message_counter % REKEY_INTERVAL).to_le_bytes();
+        // The nonce is the packet counter within the current rekey epoch
+        // (4-byte LE) followed by the rekey epoch itself (8-byte LE). Laying the
+        // epoch across the full 8 bytes keeps the layout valid for the entire
+        // 64-bit message space.
+        let counter_mod = ((self.message_counter % REKEY_INTERVAL as u64) as u32).to_le_bytes();
+        let counter_div = (self.message_counter / REKEY_INTERVAL as u64).to_le_bytes();
         let mut nonce = counter_mod.to_vec();
         nonce.extend(counter_div); // mod slice then div slice
         let cipher =
@@ -93,25 +96,24 @@ impl FSChaCha20Poly1305 {
                 ciphertext.to_vec()
             }
         };
-        if (self.message_counter + 1) % REKEY_INTERVAL == 0 {
+        if (self.message_counter + 1) % REKEY_INTERVAL as u64 == 0 {
             let mut rekey_nonce = REKEY_INITIAL_NONCE.to_vec();
-            let mut counter_div = (self.message_counter / REKEY_INTERVAL)
-                .to_le_bytes()
-                .to_vec();
-            counter_div.extend([0u8; 4]);
-            let counter_mod = (self.message_counter % REKEY_INTERVAL).to_le_bytes();
-            let mut nonce = counter_mod.to_vec();
-            nonce.extend(counter_div);
-            rekey_nonce.extend(nonce[4..].to_vec());
+            let counter_div = (self.message_counter / REKEY_INTERVAL as u64).to_le_bytes();
+            rekey_nonce.extend(counter_div);
             let mut plaintext = [0u8; 32];
             let cipher = ChaCha20Poly1305::new(
                 self.key,
-                rekey_nonce.try_into().expect("Nonce is malformed."),
+                rekey_nonce.as_slice().try_into().expect("Nonce is malformed."),
             );
             cipher
                 .encrypt(&mut plaintext, Some(&aad))
                 .map_err(|_| Error::Encryption)?;
+            // Clear the previous key before overwriting it, and wipe the
+            // transient scratch buffers holding key-derived bytes.
+            self.key.zeroize();
             self.key = plaintext;
+            plaintext.zeroize();
+            rekey_nonce.zeroize();
         }
         self.message_counter += 1;
         Ok(converted_ciphertext)
@@ -131,7 +133,7 @@ impl FSChaCha20Poly1305 {
 ///
 /// FSChaCha20 is used for lengths in BIP324. Should be noted that the lengths are still
 /// implicitly authenticated by the message packets.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Zeroize, ZeroizeOnDrop)]
 pub struct FSChaCha20 {
     key: [u8; 32],
     block_counter: u32,
@@ -160,9 +162,61 @@ impl FSChaCha20 {
             cipher.seek(self.block_counter);
             cipher.apply_keystream(&mut key_buffer);
             self.block_counter = 0;
+            // Replace the old key and wipe the transient key buffer.
+            self.key.zeroize();
             self.key = key_buffer;
+            key_buffer.zeroize();
         }
         self.chunk_counter += 1;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Encrypt with `sender` and decrypt with `receiver`, asserting the round
+    // trip recovers the plaintext.
+    fn round_trip(sender: &mut FSChaCha20Poly1305, receiver: &mut FSChaCha20Poly1305) {
+        let plaintext = alloc::vec![0xABu8; 42];
+        let ciphertext = sender.encrypt(Vec::new(), plaintext.clone()).unwrap();
+        let decrypted = receiver.decrypt(Vec::new(), ciphertext).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn rekey_increments_epoch() {
+        let mut cipher = FSChaCha20Poly1305::new([0u8; 32]);
+        // The rekey fires on the packet whose counter is `REKEY_INTERVAL - 1`,
+        // rotating the key and advancing the epoch for subsequent packets.
+        cipher.message_counter = REKEY_INTERVAL as u64 - 1;
+        cipher.encrypt(Vec::new(), alloc::vec![0u8; 8]).unwrap();
+        assert_ne!(cipher.key, [0u8; 32]);
+        assert_eq!(cipher.message_counter, REKEY_INTERVAL as u64);
+    }
+
+    #[test]
+    fn round_trip_past_u32_max() {
+        // A counter well beyond `u32::MAX` must land in a valid nonce so that
+        // long-running connections neither panic nor reuse a nonce.
+        let counter = u32::MAX as u64 + 10;
+        let mut sender = FSChaCha20Poly1305::new([7u8; 32]);
+        let mut receiver = FSChaCha20Poly1305::new([7u8; 32]);
+        sender.message_counter = counter;
+        receiver.message_counter = counter;
+        round_trip(&mut sender, &mut receiver);
+    }
+
+    #[test]
+    fn epoch_spans_full_eight_bytes() {
+        // An epoch that overflows a 4-byte field still produces a valid nonce,
+        // which the previous `[0u8; 4]` padding could not represent.
+        let counter = (u32::MAX as u64 + 1) * REKEY_INTERVAL as u64;
+        let mut sender = FSChaCha20Poly1305::new([3u8; 32]);
+        let mut receiver = FSChaCha20Poly1305::new([3u8; 32]);
+        sender.message_counter = counter;
+        receiver.message_counter = counter;
+        round_trip(&mut sender, &mut receiver);
+    }
+}
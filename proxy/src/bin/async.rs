@@ -5,7 +5,6 @@ use tokio::net::{TcpListener, TcpStream};
 use tokio::select;
 
 /// Validate and bootstrap proxy connection.
-#[allow(clippy::unused_io_amount)]
 async fn proxy_conn(mut client: TcpStream) -> Result<(), bip324_proxy::Error> {
     let remote_ip = bip324_proxy::peek_addr(&client).await?;
 
@@ -42,17 +41,17 @@ async fn proxy_conn(mut client: TcpStream) -> Result<(), bip324_proxy::Error> {
     remote.write_all(&local_garbage_terminator_message).await?;
 
     println!("Authenticating garbage and version packet.");
-    // TODO: Make this robust.
-    let mut remote_garbage_and_version = vec![0u8; 5000];
-    remote.read(&mut remote_garbage_and_version).await?;
-    let packet_handler = handshake
-        .authenticate_garbage_and_version(&remote_garbage_and_version)
-        .expect("authenticated garbage");
+    let authenticated =
+        bip324_proxy::read_and_authenticate(handshake, &mut remote).await?;
+    let packet_handler = authenticated.packet_handler;
     println!("Channel authenticated.");
 
     println!("Splitting channels.");
     let (mut client_reader, mut client_writer) = client.split();
-    let (mut remote_reader, mut remote_writer) = remote.split();
+    let (remote_reader, mut remote_writer) = remote.split();
+    // Any transport bytes coalesced into the handshake read must be replayed
+    // ahead of the socket so the first message is not lost.
+    let mut remote_reader = (&authenticated.remainder[..]).chain(remote_reader);
     let (mut decrypter, mut encrypter) = packet_handler.split();
 
     println!("Setting up proxy loop.");
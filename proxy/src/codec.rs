@@ -0,0 +1,106 @@
+//! A [`tokio_util::codec`]-style adapter over a BIP324 [`PacketHandler`].
+//!
+//! Modeled after the way the obfs4/o5 pluggable transports wrap their ciphers
+//! in a tokio-util codec: the framing logic lives in the [`Decoder`]/[`Encoder`]
+//! implementations and the byte plumbing is left to [`tokio_util::codec::Framed`].
+//! In place of fixed-buffer `read`/`read_exact` calls, it offers a state
+//! machine that tracks partial reads correctly.
+
+use bip324::{Decrypter, Encrypter, PacketHandler, ReceivedMessage};
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::Error;
+
+/// Number of bytes used for the FSChaCha20-obscured length prefix.
+const LENGTH_BYTES: usize = 3;
+/// Length of the Poly1305 authentication tag appended to every packet.
+const TAG_BYTES: usize = 16;
+
+/// Codec which encrypts outbound messages and decrypts inbound v2 packets.
+///
+/// Construct one from a completed handshake's [`PacketHandler`] and hand it to
+/// [`tokio_util::codec::Framed`]:
+///
+/// ```ignore
+/// let framed = Framed::new(stream, Bip324Codec::new(packet_handler));
+/// ```
+pub struct Bip324Codec {
+    decrypter: Decrypter,
+    encrypter: Encrypter,
+    /// Decrypted length of the packet currently being assembled, if the length
+    /// prefix has already been consumed from the stream. Retained across
+    /// `decode` calls so the length keystream is only advanced once per packet.
+    expected_payload: Option<usize>,
+}
+
+impl Bip324Codec {
+    /// Wrap a split [`PacketHandler`] in a codec.
+    pub fn new(handler: PacketHandler) -> Self {
+        let (decrypter, encrypter) = handler.split();
+        Self {
+            decrypter,
+            encrypter,
+            expected_payload: None,
+        }
+    }
+}
+
+impl Decoder for Bip324Codec {
+    // `None` is yielded for decoy packets so callers can skip them without
+    // distinguishing "connection idle" from "peer sent a decoy."
+    type Item = Option<ReceivedMessage>;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // Only touch the length keystream once we are committed to a frame, and
+        // only once even if the payload arrives across several reads.
+        let payload_len = match self.expected_payload {
+            Some(len) => len,
+            None => {
+                if src.len() < LENGTH_BYTES {
+                    return Ok(None);
+                }
+                let mut length_bytes = [0u8; LENGTH_BYTES];
+                length_bytes.copy_from_slice(&src[..LENGTH_BYTES]);
+                let len = self.decrypter.decrypt_len(length_bytes);
+                src.advance(LENGTH_BYTES);
+                self.expected_payload = Some(len);
+                len
+            }
+        };
+
+        // Wait for the full AEAD payload (ciphertext plus tag) before decrypting.
+        if src.len() < payload_len + TAG_BYTES {
+            src.reserve(payload_len + TAG_BYTES - src.len());
+            return Ok(None);
+        }
+
+        let packet = src.split_to(payload_len + TAG_BYTES);
+        self.expected_payload = None;
+        let message = self
+            .decrypter
+            .decrypt(packet.to_vec(), None)
+            .map_err(Error::Cipher)?;
+
+        // Decoys carry no message; surface them as an inner `None`.
+        match message.message {
+            Some(_) => Ok(Some(Some(message))),
+            None => Ok(Some(None)),
+        }
+    }
+}
+
+impl Encoder<Vec<u8>> for Bip324Codec {
+    type Error = Error;
+
+    fn encode(&mut self, item: Vec<u8>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let packet = self
+            .encrypter
+            .encrypt(item, None, false)
+            .map_err(Error::Cipher)?;
+        dst.reserve(packet.len());
+        dst.put_slice(&packet);
+        Ok(())
+    }
+}
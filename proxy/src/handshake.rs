@@ -0,0 +1,123 @@
+//! Incremental, bounded reader for the peer's garbage and version packets.
+//!
+//! The one-shot `remote.read(&mut [0u8; 5000])` in the proxy could neither
+//! guarantee that the garbage terminator had actually arrived nor bound the
+//! read at the protocol maximum. This turns the authentication step into a
+//! streaming state machine that pulls bytes until the terminator is seen,
+//! caps the accumulated garbage at the BIP324 limit, and only then decrypts
+//! the garbage-authenticated decoys and the version packet.
+
+use bip324::Handshake;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::Error;
+
+/// Maximum number of garbage bytes a peer may send before the terminator,
+/// per BIP324. Anything past this is treated as a malicious peer.
+const MAX_GARBAGE_LEN: usize = 4095;
+/// Length of the garbage terminator that marks the end of the garbage bytes.
+const GARBAGE_TERMINATOR_LEN: usize = 16;
+/// Upper bound on the decoy/version packet region that follows the terminator,
+/// used to stop reading from a peer that never sends a decryptable version
+/// packet.
+const MAX_VERSION_LEN: usize = 4096;
+/// Smallest possible v2 packet: 3-byte length prefix plus the 16-byte AEAD tag
+/// over empty contents. The version packet can be no shorter.
+const MIN_PACKET_LEN: usize = 3 + 16;
+
+/// A completed, authenticated handshake along with any bytes that were read
+/// past the version packet.
+pub struct Authenticated {
+    /// The established codec handler.
+    pub packet_handler: bip324::PacketHandler,
+    /// Bytes read from the socket after the version packet. These belong to the
+    /// transport stream and must be fed to the codec/`Framed` read buffer before
+    /// reading further from the socket, or the first message would be lost.
+    pub remainder: Vec<u8>,
+}
+
+/// Incrementally read from `reader` until the responder garbage terminator is
+/// found, then authenticate the garbage, any decoy packets, and the version
+/// packet, returning the established [`bip324::PacketHandler`] together with any
+/// transport bytes that arrived in the same read.
+///
+/// The `handshake` must already have its materials completed so that the
+/// session key material (and therefore the expected terminator) is known.
+pub async fn read_and_authenticate<R>(
+    handshake: Handshake,
+    reader: &mut R,
+) -> Result<Authenticated, Error>
+where
+    R: AsyncRead + Unpin,
+{
+    let terminator = handshake
+        .session_keys()
+        .ok_or(Error::HandshakeIncomplete)?
+        .responder_garbage_terminator;
+
+    let mut buffer = Vec::with_capacity(MAX_GARBAGE_LEN + GARBAGE_TERMINATOR_LEN);
+    // Index of the first byte not yet scanned for the terminator window.
+    let mut scanned = 0;
+    // Index just past the garbage terminator once it has been located.
+    let mut terminator_end: Option<usize> = None;
+
+    loop {
+        let read = reader.read_buf(&mut buffer).await?;
+        if read == 0 {
+            return Err(Error::UnexpectedEof);
+        }
+
+        if terminator_end.is_none() {
+            // Scan each new 16-byte window for the terminator. Back up to the
+            // start of the last possible window so a terminator split across
+            // two reads is still caught.
+            let start = scanned.saturating_sub(GARBAGE_TERMINATOR_LEN - 1);
+            if let Some(offset) = buffer[start..]
+                .windows(GARBAGE_TERMINATOR_LEN)
+                .position(|window| window == terminator)
+            {
+                let end = start + offset + GARBAGE_TERMINATOR_LEN;
+                // A single read may carry more than the limit before the
+                // terminator, so bound the garbage here as well.
+                if end - GARBAGE_TERMINATOR_LEN > MAX_GARBAGE_LEN {
+                    return Err(Error::GarbageTooLong);
+                }
+                terminator_end = Some(end);
+            } else {
+                scanned = buffer.len();
+                // The garbage (everything before the terminator) is bounded; if
+                // we have accumulated more than the limit without seeing it,
+                // the peer is hostile.
+                if buffer.len().saturating_sub(GARBAGE_TERMINATOR_LEN) > MAX_GARBAGE_LEN {
+                    return Err(Error::GarbageTooLong);
+                }
+                continue;
+            }
+        }
+
+        // The terminator is in hand, but the decoy packets and the version
+        // packet follow it and may still be in flight, and a peer may have
+        // coalesced the first transport packet into the same segment. Search for
+        // the shortest prefix that authenticates: a prefix one byte short of the
+        // version packet's end fails to decrypt, and the exact end is the first
+        // length that succeeds, so anything beyond it is transport remainder.
+        let min_end = terminator_end.expect("terminator located") + MIN_PACKET_LEN;
+        for end in min_end..=buffer.len() {
+            // Clone so a failed, short-buffer attempt leaves cipher state intact.
+            if let Ok(packet_handler) =
+                handshake.clone().authenticate_garbage_and_version(&buffer[..end])
+            {
+                return Ok(Authenticated {
+                    packet_handler,
+                    remainder: buffer[end..].to_vec(),
+                });
+            }
+        }
+
+        // No prefix authenticated yet; keep reading unless the peer has blown
+        // past the bound without presenting a decryptable version packet.
+        if buffer.len() > MAX_GARBAGE_LEN + GARBAGE_TERMINATOR_LEN + MAX_VERSION_LEN {
+            return Err(Error::GarbageTooLong);
+        }
+    }
+}
@@ -1,14 +1,13 @@
 //! HMAC-based Extract-and-Expand Key Derivation Function (HKDF).
 //!
-//! The interface is limited to the BIP324 use case for now. This
-//! includes hardcoding to the SHA256 hash implementation, as well
-//! as requiring an extract step.
+//! The machinery is generic over the backing hash function. BIP324 uses the
+//! [`HkdfSha256`] instantiation, but the extract/expand steps work for any
+//! [`Hash`], which lets the KDF back other protocols (e.g. Noise-style
+//! key ratchets via [`HkdfSha256::mix_key`]).
 
 use bitcoin_hashes::{sha256, Hash, HashEngine, Hmac, HmacEngine};
 use core::fmt;
 
-// Hardcoded hash length for SHA256 backed implementation.
-const HASH_LENGTH_BYTES: usize = sha256::Hash::LEN;
 // Output keying material max length multiple.
 const MAX_OUTPUT_BLOCKS: usize = 255;
 
@@ -25,16 +24,22 @@ impl fmt::Display for InvalidLength {
 impl std::error::Error for InvalidLength {}
 
 /// HMAC-based Extract-and-Expand Key Derivation Function (HKDF).
-pub struct Hkdf {
+///
+/// The hash function defaults to SHA256 in annotated positions; BIP324 call
+/// sites use the [`HkdfSha256`] alias so the associated functions can resolve
+/// the hash without an explicit turbofish.
+pub struct Hkdf<H: Hash = sha256::Hash> {
     /// Pseudorandom key based on the extract step.
-    prk: [u8; HASH_LENGTH_BYTES],
+    prk: H::Bytes,
 }
 
-impl Hkdf {
+/// The SHA256-backed instantiation used by BIP324.
+pub type HkdfSha256 = Hkdf<sha256::Hash>;
+
+impl<H: Hash> Hkdf<H> {
     /// Initialize a HKDF by performing the extract step.
     pub fn extract(salt: &[u8], ikm: &[u8]) -> Self {
-        // Hardcoding SHA256 for now, might be worth parameterizing hash function.
-        let mut hmac_engine: HmacEngine<sha256::Hash> = HmacEngine::new(salt);
+        let mut hmac_engine: HmacEngine<H> = HmacEngine::new(salt);
         hmac_engine.input(ikm);
         Self {
             prk: Hmac::from_engine(hmac_engine).to_byte_array(),
@@ -43,40 +48,42 @@ impl Hkdf {
 
     /// Expand the key to generate output key material in okm.
     pub fn expand(&self, info: &[u8], okm: &mut [u8]) -> Result<(), InvalidLength> {
+        let hash_length_bytes = <H as Hash>::LEN;
+
         // Length of output keying material must be less than 255 * hash length.
-        if okm.len() > (MAX_OUTPUT_BLOCKS * HASH_LENGTH_BYTES) {
+        if okm.len() > (MAX_OUTPUT_BLOCKS * hash_length_bytes) {
             return Err(InvalidLength);
         }
 
         // Counter starts at "1" based on RFC5869 spec and is committed to in the hash.
         let mut counter = 1u8;
         // Ceiling calculation for the total number of blocks (iterations) required for the expand.
-        let total_blocks = (okm.len() + HASH_LENGTH_BYTES - 1) / HASH_LENGTH_BYTES;
+        let total_blocks = (okm.len() + hash_length_bytes - 1) / hash_length_bytes;
 
         while counter <= total_blocks as u8 {
-            let mut hmac_engine: HmacEngine<sha256::Hash> = HmacEngine::new(&self.prk);
+            let mut hmac_engine: HmacEngine<H> = HmacEngine::new(self.prk.as_ref());
 
             // First block does not have a previous block,
             // all other blocks include last block in the HMAC input.
             if counter != 1u8 {
-                let previous_start_index = (counter as usize - 2) * HASH_LENGTH_BYTES;
-                let previous_end_index = (counter as usize - 1) * HASH_LENGTH_BYTES;
+                let previous_start_index = (counter as usize - 2) * hash_length_bytes;
+                let previous_end_index = (counter as usize - 1) * hash_length_bytes;
                 hmac_engine.input(&okm[previous_start_index..previous_end_index]);
             }
             hmac_engine.input(info);
             hmac_engine.input(&[counter]);
 
             let t = Hmac::from_engine(hmac_engine);
-            let start_index = (counter as usize - 1) * HASH_LENGTH_BYTES;
+            let start_index = (counter as usize - 1) * hash_length_bytes;
             // Last block might not take full hash length.
             let end_index = if counter == (total_blocks as u8) {
                 okm.len()
             } else {
-                counter as usize * HASH_LENGTH_BYTES
+                counter as usize * hash_length_bytes
             };
 
             okm[start_index..end_index]
-                .copy_from_slice(&t.to_byte_array()[0..(end_index - start_index)]);
+                .copy_from_slice(&t.to_byte_array().as_ref()[0..(end_index - start_index)]);
 
             counter += 1;
         }
@@ -85,6 +92,27 @@ impl Hkdf {
     }
 }
 
+impl HkdfSha256 {
+    /// Noise-style key-mixing helper for iterative key-ratchet constructions.
+    ///
+    /// Runs HKDF-extract with the current 32-byte chaining key `ck` as the salt
+    /// and `data` as the input keying material, expands 64 bytes, and splits the
+    /// result into a new chaining key (first 32 bytes) and a symmetric key `k`
+    /// (last 32 bytes), returning `(new_ck, k)`.
+    pub fn mix_key(ck: [u8; 32], data: &[u8]) -> ([u8; 32], [u8; 32]) {
+        let hkdf = Self::extract(&ck, data);
+        let mut okm = [0u8; 64];
+        hkdf.expand(&[], &mut okm)
+            .expect("64 bytes is within the HKDF output bound");
+
+        let mut new_ck = [0u8; 32];
+        let mut k = [0u8; 32];
+        new_ck.copy_from_slice(&okm[..32]);
+        k.copy_from_slice(&okm[32..]);
+        (new_ck, k)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,7 +123,7 @@ mod tests {
         let ikm = hex::decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap();
         let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
 
-        let hkdf = Hkdf::extract(&salt, &ikm);
+        let hkdf = HkdfSha256::extract(&salt, &ikm);
         let mut okm = [0u8; 42];
         hkdf.expand(&info, &mut okm).unwrap();
 
@@ -117,7 +145,7 @@ mod tests {
             "b0b1b2b3b4b5b6b7b8b9babbbcbdbebfc0c1c2c3c4c5c6c7c8c9cacbcccdcecfd0d1d2d3d4d5d6d7d8d9dadbdcdddedfe0e1e2e3e4e5e6e7e8e9eaebecedeeeff0f1f2f3f4f5f6f7f8f9fafbfcfdfeff"
         ).unwrap();
 
-        let hkdf = Hkdf::extract(&salt, &ikm);
+        let hkdf = HkdfSha256::extract(&salt, &ikm);
         let mut okm = [0u8; 82];
         hkdf.expand(&info, &mut okm).unwrap();
 
@@ -133,10 +161,28 @@ mod tests {
         let ikm = hex::decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap();
         let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
 
-        let hkdf = Hkdf::extract(&salt, &ikm);
+        let hkdf = HkdfSha256::extract(&salt, &ikm);
         let mut okm = [0u8; 256 * 32];
         let e = hkdf.expand(&info, &mut okm);
 
         assert!(e.is_err());
     }
+
+    #[test]
+    fn test_mix_key_splits_chaining_key() {
+        let ck = [0x42u8; 32];
+        let (new_ck, k) = HkdfSha256::mix_key(ck, b"input keying material");
+
+        // The two halves come from a single 64-byte expansion, so they differ
+        // from the input and from each other.
+        assert_ne!(new_ck, ck);
+        assert_ne!(new_ck, k);
+
+        // Equivalent to extracting and expanding 64 bytes manually.
+        let hkdf = HkdfSha256::extract(&ck, b"input keying material");
+        let mut okm = [0u8; 64];
+        hkdf.expand(&[], &mut okm).unwrap();
+        assert_eq!(&okm[..32], &new_ck);
+        assert_eq!(&okm[32..], &k);
+    }
 }
@@ -1,6 +1,7 @@
 use crate::PacketHandler;
 use alloc::vec::Vec;
 use secp256k1::{ellswift::ElligatorSwift, SecretKey};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 #[derive(Debug)]
 pub enum NetworkMagic {
@@ -15,6 +16,15 @@ pub struct EcdhPoint {
     pub(crate) elligator_swift: ElligatorSwift,
 }
 
+// `ElligatorSwift` is public material, but the ECDH secret key must not linger
+// in freed memory. `secp256k1` does not expose a `Zeroize` impl, so clear the
+// secret key directly rather than deriving `ZeroizeOnDrop`.
+impl Drop for EcdhPoint {
+    fn drop(&mut self) {
+        self.secret_key.non_secure_erase();
+    }
+}
+
 /// The result of initiating a handshake.
 #[derive(Clone, Debug)]
 pub struct InitiatorHandshake {
@@ -45,7 +55,7 @@ pub struct CompleteHandshake {
 }
 
 /// All keys derived from the ECDH.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct SessionKeyMaterial {
     /// A unique ID to identify a connection.
     pub session_id: [u8; 32],